@@ -1,12 +1,13 @@
 use crate::container::ComponentMap;
 use crate::module::{HasComponent, Module};
-use crate::provider::ProviderFn;
+use crate::provider::{ProviderFn, Scope};
+use crate::request::Provide;
 use crate::ContainerBuilder;
 use crate::Interface;
 use crate::Provider;
 use crate::{HasProvider, ProvidedInterface};
 use std::error::Error;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// Resolves services associated with a [`Module`]. A `Container` is built by a
 /// [`ContainerBuilder`], or through the shortcut [`Container::default`]
@@ -17,6 +18,12 @@ use std::sync::Arc;
 pub struct Container<M: Module> {
     pub(crate) module: M,
     pub(crate) provider_overrides: ComponentMap,
+    #[cfg(feature = "async")]
+    pub(crate) async_provider_overrides: ComponentMap,
+    /// Cache of already-built [`Scope::Scoped`] providers, keyed by interface `TypeId`.
+    ///
+    /// [`Scope::Scoped`]: ../provider/enum.Scope.html#variant.Scoped
+    pub(crate) scoped_cache: Mutex<ComponentMap>,
 }
 
 impl<M: Module> Default for Container<M> {
@@ -64,10 +71,11 @@ impl<M: Module> Container<M> {
     /// Each call will create a new instance of the service.
     ///
     /// # Errors
-    /// Returns a [Error::ResolveError] if the provider failed while creating
-    /// the service.
+    /// Returns a [Error::ResolveError] if the provider failed while creating the service, or a
+    /// [Error::CircularDependency] if this provider (transitively) depends on itself.
     ///
     /// [Error::ResolveError]: enum.Error.html
+    /// [Error::CircularDependency]: enum.Error.html
     ///
     /// # Examples
     /// ```
@@ -99,7 +107,190 @@ impl<M: Module> Container<M> {
         self.provider_overrides
             .get::<ProviderFn<M, I>>()
             .map(|provider_fn| provider_fn(self))
-            .unwrap_or_else(|| M::Impl::provide(self))
+            .unwrap_or_else(|| self.module.provide())
+    }
+
+    /// Create a service using the async provider registered with the interface `I`.
+    /// Each call will create a new instance of the service, awaiting the provider's
+    /// work rather than blocking the current thread.
+    ///
+    /// # Errors
+    /// Returns the error from the async provider (or its override) if it failed while creating
+    /// the service. `provide_async` passes that error through as it is: it adds no cycle
+    /// detection and no [Error::ResolveError] path.
+    ///
+    /// [Error::ResolveError]: enum.Error.html
+    ///
+    /// # Examples
+    /// There's no `#[derive(AsyncProvider)]` yet (the `module!`/derive machinery hasn't been
+    /// taught about async providers), so for now `AsyncProvider` is implemented by hand:
+    /// ```ignore
+    /// # use shaku::{module, AsyncProvider, Container, ProvidedInterface};
+    /// # use std::sync::Arc;
+    /// #
+    /// # trait Foo: ProvidedInterface {}
+    /// #
+    /// # struct FooImpl;
+    /// # impl Foo for FooImpl {}
+    /// #
+    /// # impl AsyncProvider<TestModule, dyn Foo> for FooImpl {
+    /// #     fn provide(
+    /// #         _module: &TestModule,
+    /// #     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Box<dyn Foo>, Box<dyn std::error::Error>>> + Send>> {
+    /// #         Box::pin(async { Ok(Box::new(FooImpl) as Box<dyn Foo>) })
+    /// #     }
+    /// # }
+    /// #
+    /// # module! {
+    /// #     TestModule {
+    /// #         components = [],
+    /// #         providers = []
+    /// #     }
+    /// # }
+    /// #
+    /// # async fn example() {
+    /// # let container = Container::<TestModule>::default();
+    /// #
+    /// let foo: Box<dyn Foo> = container.provide_async::<dyn Foo>().await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn provide_async<I: ProvidedInterface + ?Sized>(
+        &self,
+    ) -> Result<Box<I>, Box<dyn Error + 'static>>
+    where
+        M: crate::provider::HasAsyncProvider<I>,
+    {
+        self.async_provider_overrides
+            .get::<crate::provider::AsyncProviderFn<M, I>>()
+            .map(|provider_fn| provider_fn(self))
+            .unwrap_or_else(|| self.module.provide_async())
+            .await
+    }
+
+    /// Create a service using the provider registered with the interface `I`, respecting its
+    /// [`Scope`]. For [`Scope::Transient`] providers (the default) this behaves exactly like
+    /// [`Container::provide`]. For [`Scope::Scoped`] providers, the first call builds the
+    /// service and caches it on this `Container`; subsequent calls return a clone of the same
+    /// `Arc` instead of rebuilding it.
+    ///
+    /// [`Scope::Transient`] providers never touch the scoped-provider cache lock at all (there's
+    /// nothing to cache), so they don't serialize behind whatever `Scope::Scoped` provider
+    /// another thread happens to be building through this same `Container`. For
+    /// [`Scope::Scoped`] providers, the cache lock is held for the entire check-build-insert
+    /// sequence, so two threads racing to build the same `I` for the first time can't both win
+    /// and both build an instance. One consequence: a `Scope::Scoped` provider's own construction
+    /// must not call `provide_scoped` again on this same `Container` for any `Scope::Scoped`
+    /// interface, or it will deadlock on this lock.
+    ///
+    /// # Errors
+    /// Returns a [Error::ResolveError] if the provider failed while creating
+    /// the service.
+    ///
+    /// [`Scope`]: ../provider/enum.Scope.html
+    /// [`Scope::Transient`]: ../provider/enum.Scope.html#variant.Transient
+    /// [`Scope::Scoped`]: ../provider/enum.Scope.html#variant.Scoped
+    /// [`Container::provide`]: struct.Container.html#method.provide
+    /// [Error::ResolveError]: enum.Error.html
+    ///
+    /// # Examples
+    /// There's no `#[shaku(scope = scoped)]` derive attribute yet, so for now [`Scope::Scoped`]
+    /// is opted into by overriding [`Provider::SCOPE`] in a hand-written `impl Provider`:
+    ///
+    /// [`Provider::SCOPE`]: trait.Provider.html#associatedconstant.SCOPE
+    /// ```ignore
+    /// # use shaku::{module, Container, ProvidedInterface, Provider, Scope};
+    /// # use std::sync::Arc;
+    /// #
+    /// # trait Foo: ProvidedInterface {}
+    /// #
+    /// # struct FooImpl;
+    /// # impl Foo for FooImpl {}
+    /// #
+    /// # impl Provider<TestModule, dyn Foo> for FooImpl {
+    /// #     const SCOPE: Scope = Scope::Scoped;
+    /// #
+    /// #     fn provide(_module: &TestModule) -> Result<Box<dyn Foo>, Box<dyn std::error::Error>> {
+    /// #         Ok(Box::new(FooImpl))
+    /// #     }
+    /// # }
+    /// #
+    /// # module! {
+    /// #     TestModule {
+    /// #         components = [],
+    /// #         providers = []
+    /// #     }
+    /// # }
+    /// #
+    /// # let container = Container::<TestModule>::default();
+    /// #
+    /// let foo: Arc<dyn Foo> = container.provide_scoped::<dyn Foo>().unwrap();
+    /// ```
+    pub fn provide_scoped<I: ProvidedInterface + ?Sized>(
+        &self,
+    ) -> Result<Arc<I>, Box<dyn Error + 'static>>
+    where
+        M: HasProvider<I>,
+    {
+        if M::Impl::SCOPE != Scope::Scoped {
+            return Ok(Arc::from(self.provide::<I>()?));
+        }
+
+        let mut scoped_cache = self.scoped_cache.lock().unwrap();
+
+        if let Some(cached) = scoped_cache.get::<Arc<I>>() {
+            return Ok(Arc::clone(cached));
+        }
+
+        let instance: Arc<I> = Arc::from(self.provide::<I>()?);
+        scoped_cache.insert::<Arc<I>>(Arc::clone(&instance));
+
+        Ok(instance)
+    }
+
+    /// Registers `factory` as the provider for `I`, overriding whatever provider the module
+    /// itself registers for every subsequent [`Container::provide`]/[`Container::provide_scoped`]
+    /// call on this `Container`. This is what lets a plain closure or function stand in for a
+    /// provider (via [`ServiceFactory`]) without declaring a struct and deriving `Provider`
+    /// for it. A [`Scope::Scoped`] instance that an earlier `provide_scoped` call already cached
+    /// keeps being returned by `provide_scoped`; the override only builds the ones missing from the cache.
+    ///
+    /// [`Scope::Scoped`]: ../provider/enum.Scope.html#variant.Scoped
+    /// [`Container::provide`]: struct.Container.html#method.provide
+    /// [`Container::provide_scoped`]: struct.Container.html#method.provide_scoped
+    /// [`ServiceFactory`]: ../service_factory/trait.ServiceFactory.html
+    ///
+    /// # Examples
+    /// ```
+    /// # use shaku::{module, Container, Provider, ProvidedInterface};
+    /// #
+    /// # trait Foo: ProvidedInterface {}
+    /// #
+    /// # #[derive(Provider)]
+    /// # #[shaku(interface = Foo)]
+    /// # struct FooImpl;
+    /// # impl Foo for FooImpl {}
+    /// #
+    /// # module! {
+    /// #     TestModule {
+    /// #         components = [],
+    /// #         providers = [FooImpl]
+    /// #     }
+    /// # }
+    /// #
+    /// # let mut container = Container::<TestModule>::default();
+    /// #
+    /// container.with_provider_override::<dyn Foo, _, _>(|| Box::new(FooImpl) as Box<dyn Foo>);
+    /// let foo: Box<dyn Foo> = container.provide::<dyn Foo>().unwrap();
+    /// ```
+    pub fn with_provider_override<I, F, Args>(&mut self, factory: F)
+    where
+        I: ProvidedInterface + ?Sized,
+        M: HasProvider<I>,
+        F: crate::service_factory::ServiceFactory<M, I, Args>,
+    {
+        self.provider_overrides
+            .insert::<ProviderFn<M, I>>(factory.into_provider_fn());
     }
 
     /// Get a reference to the component registered with the interface `I`.
@@ -169,4 +360,96 @@ impl<M: Module> Container<M> {
     {
         Arc::get_mut(self.module.get_mut())
     }
+
+    /// Resolves the component registered with the interface `I`, then demands a `&T` from it via
+    /// [`Provide::provide_value`]. Returns `None` if the component doesn't provide a `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use shaku::{module, Component, Container, Interface, Provide, Request};
+    /// #
+    /// # trait Foo: Interface + Provide {}
+    /// #
+    /// # #[derive(Component)]
+    /// # #[shaku(interface = Foo)]
+    /// # struct FooImpl { build_id: String }
+    /// # impl Foo for FooImpl {}
+    /// # impl Provide for FooImpl {
+    /// #     fn provide_value(&self, request: &mut Request) {
+    /// #         request.provide_ref::<String>(&self.build_id);
+    /// #     }
+    /// # }
+    /// #
+    /// # module! {
+    /// #     TestModule {
+    /// #         components = [FooImpl],
+    /// #         providers = []
+    /// #     }
+    /// # }
+    /// #
+    /// # let container = Container::<TestModule>::default();
+    /// #
+    /// let build_id: Option<&String> = container.request_ref::<dyn Foo, String>();
+    /// ```
+    pub fn request_ref<I: Interface + ?Sized + Provide, T: ?Sized + 'static>(&self) -> Option<&T>
+    where
+        M: HasComponent<I>,
+    {
+        let component = self.resolve_ref::<I>();
+        crate::request::request_ref(|request| component.provide_value(request))
+    }
+
+    /// Resolves the component registered with the interface `I`, then demands an owned `T` from
+    /// it via [`Provide::provide_value`]. Returns `None` if the component doesn't provide a `T`.
+    pub fn request_value<I: Interface + ?Sized + Provide, T: 'static>(&self) -> Option<T>
+    where
+        M: HasComponent<I>,
+    {
+        let component = self.resolve_ref::<I>();
+        crate::request::request_value(|request| component.provide_value(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::thread;
+
+    // NOTE: this does not call `Container::provide_scoped` — building a real `Container<M>`
+    // needs a full `Module` impl, which this checkout doesn't have. It only mirrors
+    // `provide_scoped`'s locking pattern against a standalone `Mutex`-guarded cache: checked and
+    // filled under a single held lock, rather than released between the check and the insert.
+    // Before the fix, `provide_scoped` re-acquired the lock between those two steps, leaving a
+    // window where several threads could all miss the cache and all build an instance. This test
+    // would NOT catch a regression in the real `provide_scoped`; once a constructible
+    // `Container`/`Module` fixture exists, replace it with one that actually calls
+    // `provide_scoped` from multiple threads against a `Scope::Scoped` provider.
+    #[test]
+    fn locking_pattern_mirroring_provide_scoped_lets_only_one_thread_build_on_a_cache_miss() {
+        let cache: Mutex<Option<Arc<AtomicUsize>>> = Mutex::new(None);
+        let build_count = AtomicUsize::new(0);
+        let threads = 8;
+        let barrier = Barrier::new(threads);
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    barrier.wait();
+
+                    let mut cache = cache.lock().unwrap();
+                    if cache.is_none() {
+                        build_count.fetch_add(1, Ordering::SeqCst);
+                        *cache = Some(Arc::new(AtomicUsize::new(0)));
+                    }
+                });
+            }
+        });
+
+        assert_eq!(
+            build_count.load(Ordering::SeqCst),
+            1,
+            "holding the lock across check-and-fill should let only one thread build"
+        );
+    }
 }
@@ -0,0 +1,104 @@
+//! This module contains shaku's error type(s)
+
+use std::error::Error as StdError;
+use std::fmt;
+
+/// Errors produced while resolving components/providers from a [`Container`].
+///
+/// [`Container`]: struct.Container.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A [`Provider::provide`] call failed, somewhere in the dependency chain rooted at the
+    /// top-level [`Container::provide`] call.
+    ///
+    /// [`Provider::provide`]: trait.Provider.html#tymethod.provide
+    /// [`Container::provide`]: struct.Container.html#method.provide
+    ResolveError(ResolveError),
+    /// Resolving a provider required resolving itself again, transitively, before the first
+    /// resolution finished. `path` is the chain of interface type names, in resolution order,
+    /// starting and ending with the interface that triggered the cycle.
+    CircularDependency {
+        /// The chain of interface type names that make up the cycle, in resolution order.
+        path: Vec<&'static str>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ResolveError(err) => write!(f, "{}", err),
+            Error::CircularDependency { path } => {
+                write!(f, "circular dependency detected: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::ResolveError(err) => Some(err.source.as_ref()),
+            Error::CircularDependency { .. } => None,
+        }
+    }
+}
+
+/// One provider entered while resolving a [`ResolveError`]'s dependency chain.
+///
+/// [`ResolveError`]: struct.ResolveError.html
+#[derive(Debug)]
+struct Frame {
+    /// The interface being resolved, ex. `"dyn my_crate::MyInterface"`.
+    interface: &'static str,
+    /// The concrete provider type resolving it, ex. `"my_crate::MyInterfaceImpl"`.
+    provider: &'static str,
+}
+
+/// A resolution failure that accumulates a [`Frame`] for every provider entered between the
+/// top-level [`Container::provide`] call and the provider whose [`Provider::provide`] actually
+/// returned the error (`source`). This turns a bare `Box<dyn Error>` from deep in a provider
+/// chain into an actionable path: which top-level interface was requested, and which dependency,
+/// transitively, is the one that actually failed.
+///
+/// [`Frame`]: struct.Frame.html
+/// [`Container::provide`]: struct.Container.html#method.provide
+/// [`Provider::provide`]: trait.Provider.html#tymethod.provide
+#[derive(Debug)]
+pub struct ResolveError {
+    /// Frames in resolution order: the top-level interface first, the failing leaf last.
+    frames: Vec<Frame>,
+    source: Box<dyn StdError + 'static>,
+}
+
+impl ResolveError {
+    /// Wraps `source` (the root cause, as returned by a `Provider::provide` impl) with no
+    /// frames yet. Frames are added as the error bubbles up through nested `provide` calls via
+    /// [`ResolveError::push_frame`].
+    ///
+    /// [`ResolveError::push_frame`]: struct.ResolveError.html#method.push_frame
+    pub(crate) fn new(source: Box<dyn StdError + 'static>) -> Self {
+        ResolveError {
+            frames: Vec::new(),
+            source,
+        }
+    }
+
+    /// Records that `interface` (resolved via `provider`) depends, transitively, on whatever
+    /// failed further down the chain. Called once per nesting level as the error propagates
+    /// back up to the top-level `Container::provide` call, so the first frame ends up being the
+    /// outermost (top-level) interface.
+    pub(crate) fn push_frame(&mut self, interface: &'static str, provider: &'static str) {
+        self.frames.insert(0, Frame { interface, provider });
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to resolve service")?;
+        for frame in &self.frames {
+            write!(f, ": {} (via {})", frame.interface, frame.provider)?;
+        }
+        write!(f, ": {}", self.source)
+    }
+}
@@ -4,6 +4,31 @@ use crate::module::ModuleInterface;
 use crate::Module;
 use std::error::Error;
 
+/// The lifetime of a provided service within a single [`Container`].
+///
+/// [`Component`]s are always singletons (the same `Arc` is returned for the lifetime of the
+/// module), and [`Provider`]s default to [`Scope::Transient`] (a new instance on every
+/// [`Container::provide`] call). [`Scope::Scoped`] sits in between: the first
+/// [`Container::provide_scoped`] call builds the service and caches it, and later
+/// `provide_scoped` calls on the same [`Container`] return a clone of the same `Arc` instead of
+/// rebuilding it. [`Container::provide`] ignores the scope and always builds a new instance.
+///
+/// [`Component`]: trait.Component.html
+/// [`Container`]: struct.Container.html
+/// [`Container::provide`]: struct.Container.html#method.provide
+/// [`Container::provide_scoped`]: struct.Container.html#method.provide_scoped
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Scope {
+    /// A new instance is built on every `provide` call. This is the default.
+    Transient,
+    /// The first `provide_scoped` call on a given [`Container`] builds the instance and caches
+    /// it; later `provide_scoped` calls on that same container return a shared clone instead of
+    /// rebuilding it.
+    ///
+    /// [`Container`]: struct.Container.html
+    Scoped,
+}
+
 /// Like [`Component`]s, providers provide a service by implementing an interface.
 ///
 /// Unlike [`Component`], `Provider` represents a temporary service. Examples include a connection
@@ -24,6 +49,15 @@ use std::error::Error;
 /// [`HasProvider`]: trait.HasProvider.html
 /// [provider getting started guide]: guide/provider/index.html
 pub trait Provider<M: Module, I: ?Sized + 'static>: 'static {
+    /// The [`Scope`] this provider is resolved with. Defaults to [`Scope::Transient`]. There's
+    /// no `#[shaku(scope = scoped)]` derive attribute yet, so set this to [`Scope::Scoped`] by
+    /// overriding the const in a hand-written `impl Provider`.
+    ///
+    /// [`Scope`]: enum.Scope.html
+    /// [`Scope::Transient`]: enum.Scope.html#variant.Transient
+    /// [`Scope::Scoped`]: enum.Scope.html#variant.Scoped
+    const SCOPE: Scope = Scope::Transient;
+
     /// Provides the service, possibly resolving other components/providers
     /// to do so.
     fn provide(module: &M) -> Result<Box<I>, Box<dyn Error>>;
@@ -44,11 +78,104 @@ pub type ProviderFn<M, I> = Box<dyn (Fn(&M) -> Result<Box<I>, Box<dyn Error>>)>;
 #[cfg(feature = "thread_safe")]
 pub type ProviderFn<M, I> = Box<dyn (Fn(&M) -> Result<Box<I>, Box<dyn Error>>) + Send + Sync>;
 
+/// Like [`Provider`], but for services whose construction is asynchronous (ex. opening a
+/// connection to a remote service or pooled database connection over the network).
+///
+/// The future returned by `provide` is `'static`, so it can't borrow `module`: dependencies are
+/// resolved from `module` before the future is built (ex. cloning a [`Component`]'s `Arc`).
+/// Another async provider's future, as returned by `HasAsyncProvider::provide_async`, borrows the
+/// module and so can't be awaited from inside it.
+///
+/// ## Generics
+/// * `M` - The module. This is used to require certain things about the module
+///   that this provider is used in, such as requiring dependencies via
+///   [`HasComponent`]/[`HasAsyncProvider`].
+/// * `I` - The trait/interface which this provider implements.
+///
+/// [`Component`]: trait.Component.html
+/// [`Provider`]: trait.Provider.html
+/// [`HasComponent`]: trait.HasComponent.html
+/// [`HasAsyncProvider`]: trait.HasAsyncProvider.html
+#[cfg(feature = "async")]
+pub trait AsyncProvider<M: Module, I: ?Sized + 'static>: 'static {
+    /// Provides the service, possibly resolving its dependencies from `module` before
+    /// building the returned future.
+    fn provide(module: &M) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Box<I>, Box<dyn Error>>> + Send>>;
+}
+
+/// The type signature of [`AsyncProvider::provide`]. This is used when overriding an
+/// async provider via [`ModuleBuilder::with_async_provider_override`]
+///
+/// [`AsyncProvider::provide`]: trait.AsyncProvider.html#tymethod.provide
+/// [`ModuleBuilder::with_async_provider_override`]: struct.ModuleBuilder.html#method.with_async_provider_override
+#[cfg(feature = "async")]
+pub type AsyncProviderFn<M, I> = Box<
+    dyn (Fn(&M) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Box<I>, Box<dyn Error>>> + Send>>)
+        + Send
+        + Sync,
+>;
+
+/// Indicates that a module contains an async provider which implements the interface.
+#[cfg(feature = "async")]
+pub trait HasAsyncProvider<I: ?Sized>: ModuleInterface {
+    /// Create a service using the async provider registered with the interface `I`.
+    /// Each call will create a new instance of the service.
+    ///
+    /// # Examples
+    /// There's no `#[derive(AsyncProvider)]` yet (the `module!`/derive machinery hasn't been
+    /// taught about async providers), so for now `AsyncProvider` is implemented by hand:
+    /// ```ignore
+    /// # use shaku::{module, HasAsyncProvider, AsyncProvider};
+    /// # use std::sync::Arc;
+    /// #
+    /// # trait Foo {}
+    /// #
+    /// # struct FooImpl;
+    /// # impl Foo for FooImpl {}
+    /// #
+    /// # impl AsyncProvider<TestModule, dyn Foo> for FooImpl {
+    /// #     fn provide(
+    /// #         _module: &TestModule,
+    /// #     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Box<dyn Foo>, Box<dyn std::error::Error>>> + Send>> {
+    /// #         Box::pin(async { Ok(Box::new(FooImpl) as Box<dyn Foo>) })
+    /// #     }
+    /// # }
+    /// #
+    /// # module! {
+    /// #     TestModule {
+    /// #         components = [],
+    /// #         providers = []
+    /// #     }
+    /// # }
+    /// #
+    /// # async fn example() {
+    /// # let module = TestModule::builder().build();
+    /// #
+    /// let foo: Box<dyn Foo> = module.provide_async().await.unwrap();
+    /// # }
+    /// ```
+    fn provide_async(
+        &self,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Box<I>, Box<dyn Error>>> + Send + '_>>;
+}
+
 /// Indicates that a module contains a provider which implements the interface.
-pub trait HasProvider<I: ?Sized>: ModuleInterface {
+pub trait HasProvider<I: ?Sized>: ModuleInterface + Module {
+    /// The concrete provider type registered with this interface.
+    type Impl: Provider<Self, I>;
+
     /// Create a service using the provider registered with the interface `I`.
     /// Each call will create a new instance of the service.
     ///
+    /// This default implementation is the single path every `provide` call funnels through,
+    /// whether it's a top-level [`Container::provide`] call or a provider resolving one of its
+    /// own dependencies via `module.provide::<Dep>()` from inside its `Provider::provide` body.
+    /// The one exception is a provider override registered on the `Container`, which
+    /// `Container::provide` calls directly.
+    /// That's what lets cycle detection and [`ResolveError`] path-building apply uniformly
+    /// regardless of which one a particular interface was reached through; implementors
+    /// shouldn't need to override it.
+    ///
     /// # Examples
     /// ```
     /// # use shaku::{module, HasProvider, Provider};
@@ -74,5 +201,10 @@ pub trait HasProvider<I: ?Sized>: ModuleInterface {
     /// let foo: Box<dyn Foo> = module.provide().unwrap();
     /// # }
     /// ```
-    fn provide(&self) -> Result<Box<I>, Box<dyn Error>>;
+    ///
+    /// [`Container::provide`]: struct.Container.html#method.provide
+    /// [`ResolveError`]: struct.ResolveError.html
+    fn provide(&self) -> Result<Box<I>, Box<dyn Error>> {
+        crate::resolution::guarded_provide::<Self, I>(self)
+    }
 }
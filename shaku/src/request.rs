@@ -0,0 +1,151 @@
+//! This module contains the [`Request`]/[`Provide`] pair, which let a resolved component or
+//! provided service expose auxiliary typed values (config snapshots, metrics handles, build
+//! metadata, ...) without widening its interface trait to carry them as regular methods.
+//!
+//! This is modeled on the generic-member-access pattern ([`std::error::Error::provide`] uses the
+//! same idea for backtraces and the like): a type-erased "demand" object is passed down to a
+//! [`Provide::provide_value`] hook, which fills it in only if it's being asked for a type it
+//! actually has.
+//!
+//! [`std::error::Error::provide`]: https://doc.rust-lang.org/std/error/trait.Error.html#method.provide
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Implemented by component/provided-service types that want to expose auxiliary typed values
+/// through [`Container::request_ref`]/[`Container::request_value`] without adding methods to
+/// their interface trait.
+///
+/// The default implementation provides nothing; implementors override it to fill in a
+/// [`Request`] for the types they support, typically with a chain of [`Request::provide_ref`]/
+/// [`Request::provide_value`] calls.
+///
+/// [`Container::request_ref`]: struct.Container.html#method.request_ref
+/// [`Container::request_value`]: struct.Container.html#method.request_value
+/// [`Request::provide_ref`]: struct.Request.html#method.provide_ref
+/// [`Request::provide_value`]: struct.Request.html#method.provide_value
+pub trait Provide {
+    /// Fills in `request` with any values this implementor can supply of the type(s) being
+    /// demanded. Implementors that don't recognize the demanded type should leave `request`
+    /// untouched.
+    #[allow(unused_variables)]
+    fn provide_value<'a>(&'a self, request: &mut Request<'a>) {}
+}
+
+/// A type-erased demand for a single value of type `T` (via [`Request::provide_value`]) or `&T`
+/// (via [`Request::provide_ref`]), passed to [`Provide::provide_value`]. See the [module-level
+/// docs](self) for the overall pattern.
+///
+/// `Request<'a>` is `#[repr(transparent)]` around (and, being unsized, effectively *is*) the
+/// erased `dyn Erased<'a>` that actually holds the demanded value, rather than a sized struct
+/// holding an `&'a mut dyn Erased<'a>` field. A sized struct can't work here: building one means
+/// borrowing a `TaggedOption` that only lives for the body of [`Request::demand`], and that
+/// borrow can't be coerced to live for the caller-chosen `'a`. Going through the trait object
+/// itself sidesteps that — [`TaggedOption::as_request`] reinterprets a `&mut TaggedOption<'a, I>`
+/// as a `&mut Request<'a>` via a raw pointer cast, which is sound precisely because the two share
+/// the same `dyn Erased<'a>` representation.
+#[repr(transparent)]
+pub struct Request<'a> {
+    erased: dyn Erased<'a> + 'a,
+}
+
+impl<'a> Request<'a> {
+    /// Provides `value` if this request is asking for an owned `T`. Returns `self` so calls can
+    /// be chained for providers that support several types.
+    pub fn provide_value<T: 'static>(&mut self, value: T) -> &mut Self {
+        self.provide::<ValueTag<T>>(value)
+    }
+
+    /// Provides `value` if this request is asking for a `&T`. Returns `self` so calls can be
+    /// chained for providers that support several types.
+    pub fn provide_ref<T: ?Sized + 'static>(&mut self, value: &'a T) -> &mut Self {
+        self.provide::<RefTag<T>>(value)
+    }
+
+    fn provide<I: Tag<'a>>(&mut self, value: I::Reified) -> &mut Self {
+        if let Some(res @ None) = self.downcast_mut::<I>() {
+            *res = Some(value);
+        }
+        self
+    }
+
+    /// Builds a request for an `I::Reified` (an owned `T` or a `&'a T`, depending on the tag),
+    /// runs `provide` against it, and returns
+    /// whatever was (or wasn't) provided.
+    fn demand<I: Tag<'a>>(provide: impl FnOnce(&mut Request<'a>)) -> Option<I::Reified> {
+        let mut tagged = TaggedOption::<'a, I>(None);
+        provide(tagged.as_request());
+        tagged.0
+    }
+
+    fn downcast_mut<I: Tag<'a>>(&mut self) -> Option<&mut Option<I::Reified>> {
+        if self.erased.tag_id() == TypeId::of::<I>() {
+            // SAFETY: `tag_id` is only ever implemented by `TaggedOption<'a, I>` to return
+            // `TypeId::of::<I>()`, so a match here means `self.erased` really does point to a
+            // `TaggedOption<'a, I>`. Casting the fat `*mut dyn Erased<'a>` pointer to the thin
+            // `*mut TaggedOption<'a, I>` one via `as` simply drops the now-redundant vtable half.
+            Some(unsafe {
+                &mut (*((&mut self.erased as *mut dyn Erased<'a>) as *mut TaggedOption<'a, I>)).0
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Resolves a value of type `T` by running `provide` against a fresh request for an owned `T`.
+pub(crate) fn request_value<'a, T: 'static>(provide: impl FnOnce(&mut Request<'a>)) -> Option<T> {
+    Request::demand::<ValueTag<T>>(provide)
+}
+
+/// Resolves a value of type `&'a T` by running `provide` against a fresh request for a `&'a T`.
+pub(crate) fn request_ref<'a, T: ?Sized + 'static>(
+    provide: impl FnOnce(&mut Request<'a>),
+) -> Option<&'a T> {
+    Request::demand::<RefTag<T>>(provide)
+}
+
+/// A marker type identifying what's being demanded: either an owned `T` ([`ValueTag`]) or a
+/// `&'a T` ([`RefTag`]). `Tag` types are themselves `'static` (they only carry `T` in
+/// `PhantomData`), which is what lets [`Request::downcast_mut`] key off `TypeId::of::<I>()` even
+/// though the thing being demanded (`I::Reified`) may borrow from `'a`.
+trait Tag<'a>: 'static {
+    /// The concrete type being demanded: `T` for [`ValueTag<T>`], `&'a T` for [`RefTag<T>`].
+    type Reified: 'a;
+}
+
+struct ValueTag<T>(PhantomData<T>);
+impl<'a, T: 'static> Tag<'a> for ValueTag<T> {
+    type Reified = T;
+}
+
+struct RefTag<T: ?Sized>(PhantomData<T>);
+impl<'a, T: ?Sized + 'static> Tag<'a> for RefTag<T> {
+    type Reified = &'a T;
+}
+
+/// A type-erased `TaggedOption<'a, I>`, downcast back to its concrete type by
+/// [`Request::downcast_mut`] once `tag_id()` confirms which `I` it actually is.
+trait Erased<'a>: 'a {
+    fn tag_id(&self) -> TypeId;
+}
+
+struct TaggedOption<'a, I: Tag<'a>>(Option<I::Reified>);
+
+impl<'a, I: Tag<'a>> Erased<'a> for TaggedOption<'a, I> {
+    fn tag_id(&self) -> TypeId {
+        TypeId::of::<I>()
+    }
+}
+
+impl<'a, I: Tag<'a>> TaggedOption<'a, I> {
+    /// Reinterprets this concrete, locally-owned `TaggedOption` as a `&mut Request<'a>`, without
+    /// the caller learning the concrete tag `I`. First an ordinary unsized coercion produces the
+    /// fat `&mut (dyn Erased<'a> + 'a)` pointer; the following raw-pointer cast to
+    /// `*mut Request<'a>` is then sound because `Request<'a>` is `#[repr(transparent)]` around
+    /// that exact trait object, so the two types share a representation.
+    fn as_request(&mut self) -> &mut Request<'a> {
+        let erased: &mut (dyn Erased<'a> + 'a) = self;
+        unsafe { &mut *(erased as *mut (dyn Erased<'a> + 'a) as *mut Request<'a>) }
+    }
+}
@@ -0,0 +1,195 @@
+//! Internal machinery shared by every `provide` entry point: [`Container::provide`] (the
+//! top-level, user-facing call) and the default [`HasProvider::provide`] implementation (what
+//! a provider's own `Provider::provide` body calls, via `module.provide::<Dep>()`, to resolve its
+//! own dependencies). Both ultimately call [`guarded_provide`], so a cycle or a deep failure is
+//! caught no matter which one a particular interface was reached through. A provider override
+//! registered on the `Container` is called directly by `Container::provide` instead; the
+//! dependencies it resolves through `module.provide::<Dep>()` still go through `guarded_provide`.
+//!
+//! [`Container::provide`]: struct.Container.html#method.provide
+//! [`HasProvider::provide`]: trait.HasProvider.html#method.provide
+
+use crate::error::Error as ShakuError;
+use crate::provider::{HasProvider, Provider};
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::error::Error;
+
+thread_local! {
+    /// The chain of interface `TypeId`s (and their names, for diagnostics) currently being
+    /// resolved on this thread, across *all* nested `provide` calls regardless of whether they
+    /// came in through [`Container::provide`] or a provider resolving its own dependencies.
+    /// Used to detect cycles in provider dependency graphs.
+    ///
+    /// [`Container::provide`]: struct.Container.html#method.provide
+    static RESOLUTION_STACK: RefCell<Vec<(TypeId, &'static str)>> = RefCell::new(Vec::new());
+}
+
+/// Pushes an interface onto [`RESOLUTION_STACK`] for the duration of its resolution, popping it
+/// again on drop (including on early return via `?`) so a failed resolution doesn't leave the
+/// interface stuck on the stack.
+struct ResolutionGuard;
+
+impl ResolutionGuard {
+    /// Pushes `I` onto the resolution stack, or returns a [`ShakuError::CircularDependency`] if
+    /// `I` is already being resolved somewhere up the stack.
+    fn enter<I: ?Sized + 'static>() -> Result<Self, ShakuError> {
+        let type_id = TypeId::of::<I>();
+        let type_name = std::any::type_name::<I>();
+
+        RESOLUTION_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(pos) = stack.iter().position(|(id, _)| *id == type_id) {
+                let mut path: Vec<&'static str> =
+                    stack[pos..].iter().map(|(_, name)| *name).collect();
+                path.push(type_name);
+                return Err(ShakuError::CircularDependency { path });
+            }
+
+            stack.push((type_id, type_name));
+            Ok(())
+        })?;
+
+        Ok(ResolutionGuard)
+    }
+}
+
+impl Drop for ResolutionGuard {
+    fn drop(&mut self) {
+        RESOLUTION_STACK.with(|stack| {
+            stack.borrow_mut().pop();
+        });
+    }
+}
+
+/// Adds a [`ShakuError::ResolveError`] frame for `interface`/`provider` to `error`, wrapping it
+/// as a fresh [`ShakuError::ResolveError`] if it isn't one already (ex. a raw error returned by a
+/// user's `Provider::provide` impl). Leaves [`ShakuError::CircularDependency`] untouched, since
+/// wrapping it as a resolve error would bury the information that actually matters for that case.
+///
+/// [`ShakuError::ResolveError`]: ../error/enum.Error.html#variant.ResolveError
+/// [`ShakuError::CircularDependency`]: ../error/enum.Error.html#variant.CircularDependency
+fn push_resolve_frame(
+    error: Box<dyn Error + 'static>,
+    interface: &'static str,
+    provider: &'static str,
+) -> Box<dyn Error + 'static> {
+    let mut resolve_error = match error.downcast::<ShakuError>() {
+        Ok(shaku_error) => match *shaku_error {
+            ShakuError::CircularDependency { path } => {
+                return Box::new(ShakuError::CircularDependency { path });
+            }
+            ShakuError::ResolveError(resolve_error) => resolve_error,
+        },
+        Err(source) => crate::error::ResolveError::new(source),
+    };
+
+    resolve_error.push_frame(interface, provider);
+    Box::new(ShakuError::ResolveError(resolve_error))
+}
+
+/// Resolves `I` from `module`, guarding against cycles and accumulating a [`ResolveError`] frame
+/// if resolution fails. This is the single path both [`Container::provide`] and the default
+/// [`HasProvider::provide`] implementation funnel through, so nested provider-to-provider
+/// resolution is guarded exactly the same way a top-level `Container::provide` call is.
+///
+/// [`ResolveError`]: ../error/struct.ResolveError.html
+/// [`Container::provide`]: struct.Container.html#method.provide
+/// [`HasProvider::provide`]: trait.HasProvider.html#method.provide
+pub(crate) fn guarded_provide<M, I>(module: &M) -> Result<Box<I>, Box<dyn Error + 'static>>
+where
+    M: HasProvider<I>,
+    I: ?Sized + 'static,
+{
+    let _guard = ResolutionGuard::enter::<I>()?;
+
+    <M::Impl as Provider<M, I>>::provide(module).map_err(|source| {
+        push_resolve_frame(
+            source,
+            std::any::type_name::<I>(),
+            std::any::type_name::<M::Impl>(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{push_resolve_frame, ResolutionGuard};
+    use crate::error::Error as ShakuError;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Root;
+
+    impl fmt::Display for Root {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "connection refused")
+        }
+    }
+
+    impl std::error::Error for Root {}
+
+    /// Mirrors what happens as a real error bubbles up through nested
+    /// `module.provide::<Dep>()` calls: each level on the way back to the top-level
+    /// `Container::provide` call adds its own frame via `push_resolve_frame`, deepest first.
+    #[test]
+    fn accumulates_a_frame_per_nesting_level() {
+        let error: Box<dyn std::error::Error> = Box::new(Root);
+        let error = push_resolve_frame(error, "dyn crate_a::Leaf", "LeafImpl");
+        let error = push_resolve_frame(error, "dyn crate_a::Mid", "MidImpl");
+        let error = push_resolve_frame(error, "dyn crate_a::Top", "TopImpl");
+
+        let message = error.to_string();
+        let top = message.find("dyn crate_a::Top").unwrap();
+        let mid = message.find("dyn crate_a::Mid").unwrap();
+        let leaf = message.find("dyn crate_a::Leaf").unwrap();
+        let root_cause = message.find("connection refused").unwrap();
+
+        assert!(
+            top < mid && mid < leaf && leaf < root_cause,
+            "expected frames from top-level interface down to the failing leaf, got: {message}"
+        );
+
+        assert!(error.source().unwrap().to_string().contains("connection refused"));
+    }
+
+    #[test]
+    fn leaves_circular_dependency_errors_untouched() {
+        let error: Box<dyn std::error::Error> = Box::new(ShakuError::CircularDependency {
+            path: vec!["dyn crate_a::Foo", "dyn crate_a::Bar", "dyn crate_a::Foo"],
+        });
+
+        let error = push_resolve_frame(error, "dyn crate_a::Top", "TopImpl");
+
+        assert!(error.to_string().contains("circular dependency detected"));
+        assert!(!error.to_string().contains("TopImpl"));
+    }
+
+    // The full `Module`/`HasProvider`/derive machinery needed to build two real providers that
+    // depend on each other isn't present in this checkout, so this exercises `ResolutionGuard`
+    // directly: `Foo`'s resolution (still on the stack) resolves `Bar`, whose resolution tries to
+    // resolve `Foo` again, exactly like two providers whose `Provider::provide` bodies call back
+    // into each other via `module.provide::<Dep>()` would.
+    trait Foo {}
+    trait Bar {}
+
+    #[test]
+    fn detects_cycle_across_distinct_interfaces() {
+        let _foo_guard = ResolutionGuard::enter::<dyn Foo>().unwrap();
+        let _bar_guard = ResolutionGuard::enter::<dyn Bar>().unwrap();
+
+        let result = ResolutionGuard::enter::<dyn Foo>();
+
+        assert!(result.is_err(), "re-entering Foo while it's still on the stack should fail");
+    }
+
+    #[test]
+    fn unrelated_interfaces_do_not_conflict() {
+        let _foo_guard = ResolutionGuard::enter::<dyn Foo>().unwrap();
+
+        assert!(
+            ResolutionGuard::enter::<dyn Bar>().is_ok(),
+            "entering an interface that isn't already on the stack should succeed"
+        );
+    }
+}